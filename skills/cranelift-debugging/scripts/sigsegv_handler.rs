@@ -6,67 +6,1315 @@
 //   2. Copy this module into your test file
 //   3. Call `sigsegv_handler::install()` at the start of your test
 //   4. When crash occurs, attach with: lldb -p <pid>
+//
+// Optional: on a machine with no interactive stdin (CI, containers), add
+// `gdbstub = "0.7"` and `gdbstub_arch = "0.3"`, enable the `gdbstub`
+// feature below, and set
+// `Config::gdbstub_port` — the handler then opens a GDB Remote Serial
+// Protocol server instead of waiting on Enter, and prints the
+// `target remote :<port>` line to paste into gdb or lldb.
+//
+// Optional: add `backtrace = "0.3"` and enable the `backtrace` feature to
+// get a stack trace of the crashing thread printed with the banner — raw
+// addresses immediately, symbol names once it's safe to resolve them.
+//
+// Optional: call `on_crash`/`on_timeout` before `install()` to run your own
+// callback(s) instead of the default pause-and-print behavior (the default
+// is only registered when no callback of that kind is), and set `Config::timeout` to also catch a wedged JIT test via
+// a `SIGALRM` watchdog. Callbacks run in signal-handler context.
 
 #[cfg(unix)]
 pub mod sigsegv_handler {
-    use std::io::Read;
-    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::OnceLock;
 
     static CAUGHT: AtomicBool = AtomicBool::new(false);
+    static PAUSE_FOR_DEBUGGER: AtomicBool = AtomicBool::new(true);
 
-    extern "C" fn handler(sig: libc::c_int) {
-        // Prevent recursive signals
-        if CAUGHT.swap(true, Ordering::SeqCst) {
-            std::process::abort();
-        }
+    /// Banner text, indexed by which of the four fatal signals we catch.
+    /// Built once in `install_with_config` so the handler itself never
+    /// formats strings or allocates — everything it touches is a byte
+    /// slice it already owns.
+    static BANNERS: OnceLock<[Vec<u8>; 4]> = OnceLock::new();
+    /// Banner for the `SIGALRM` timeout path, built only when a timeout is
+    /// configured.
+    static ALARM_BANNER: OnceLock<Vec<u8>> = OnceLock::new();
+
+    const SIGNALS: [libc::c_int; 4] = [libc::SIGSEGV, libc::SIGBUS, libc::SIGFPE, libc::SIGILL];
 
-        let sig_name = match sig {
+    fn sig_index(sig: libc::c_int) -> usize {
+        SIGNALS.iter().position(|&s| s == sig).unwrap_or(0)
+    }
+
+    fn sig_name(sig: libc::c_int) -> &'static str {
+        match sig {
             libc::SIGSEGV => "SIGSEGV",
             libc::SIGBUS => "SIGBUS",
             libc::SIGFPE => "SIGFPE",
             libc::SIGILL => "SIGILL",
+            libc::SIGALRM => "SIGALRM (timeout)",
             _ => "UNKNOWN",
-        };
+        }
+    }
+
+    /// The context handed to every registered callback: everything about
+    /// the event that's cheap/safe to have captured already. Callbacks run
+    /// in signal-handler context, so the same async-signal-safety rules
+    /// that apply to `handler` apply to them — no allocation, no locking,
+    /// no blocking I/O other than raw `read`/`write` on known fds.
+    pub struct CrashContext {
+        /// The signal that fired: one of `SIGSEGV`/`SIGBUS`/`SIGFPE`/`SIGILL`
+        /// for a crash, or `SIGALRM` for a timeout.
+        pub signal: libc::c_int,
+        /// `true` if this context is from the timeout watchdog rather than
+        /// an actual fault.
+        pub is_timeout: bool,
+        /// The faulting address (`si_addr`), when available.
+        pub fault_addr: Option<u64>,
+        /// The raw `si_code` from `siginfo_t`, when available.
+        pub si_code: Option<libc::c_int>,
+        /// The crashing/interrupted thread's instruction pointer, decoded
+        /// from the `ucontext_t`, when available on this architecture.
+        pub pc: Option<u64>,
+        /// The raw `ucontext_t*` passed to the handler by the kernel.
+        /// Valid only for the duration of the callback call.
+        pub ucontext: *mut libc::c_void,
+    }
+
+    pub type CrashCallback = fn(&CrashContext);
 
-        eprintln!("\n╔══════════════════════════════════════════════════════════╗");
-        eprintln!("║  CAUGHT {sig_name} - Process paused for debugger attachment");
-        eprintln!("╠══════════════════════════════════════════════════════════╣");
-        eprintln!("║  PID: {}", std::process::id());
-        eprintln!("║                                                          ║");
-        eprintln!("║  Attach debugger:                                        ║");
-        eprintln!("║    lldb -p {}                                      ", std::process::id());
-        eprintln!("║    gdb -p {}                                       ", std::process::id());
-        eprintln!("║                                                          ║");
-        eprintln!("║  Then in debugger:                                       ║");
-        eprintln!("║    bt          # backtrace                               ║");
-        eprintln!("║    f 0         # select frame                            ║");
-        eprintln!("║    di -p       # disassemble at PC                       ║");
-        eprintln!("║    reg read    # show registers                          ║");
-        eprintln!("╠══════════════════════════════════════════════════════════╣");
-        eprintln!("║  Press Enter to continue (will crash)...                 ║");
-        eprintln!("╚══════════════════════════════════════════════════════════╝");
+    /// An append-only list of callbacks. Registration (`push`) happens
+    /// during setup, before any signal can fire, so it's free to grow the
+    /// backing `Vec` like any other allocating Rust; `run` happens from
+    /// the signal handler and only ever reads, so the two never race in
+    /// practice. `UnsafeCell` instead of a `Mutex` because taking a lock
+    /// from a signal handler risks deadlocking against the very thread it
+    /// interrupted.
+    struct CallbackRegistry {
+        slots: std::cell::UnsafeCell<Vec<CrashCallback>>,
+        count: AtomicUsize,
+    }
+
+    unsafe impl Sync for CallbackRegistry {}
+
+    impl CallbackRegistry {
+        const fn new() -> Self {
+            Self {
+                slots: std::cell::UnsafeCell::new(Vec::new()),
+                count: AtomicUsize::new(0),
+            }
+        }
+
+        fn push(&self, cb: CrashCallback) {
+            // SAFETY: only called during setup, single-threaded, before
+            // `install_with_config` arms any signal handler.
+            unsafe { (*self.slots.get()).push(cb) };
+            self.count.store(unsafe { (*self.slots.get()).len() }, Ordering::SeqCst);
+        }
+
+        fn is_empty(&self) -> bool {
+            self.count.load(Ordering::SeqCst) == 0
+        }
+
+        /// Run every registered callback in registration order. Safe to
+        /// call from a signal handler as long as every registered callback
+        /// is itself signal-safe.
+        fn run(&self, ctx: &CrashContext) {
+            let count = self.count.load(Ordering::SeqCst);
+            // SAFETY: read-only from here on; no concurrent `push` can
+            // happen once a signal is live to call `run`.
+            let slots = unsafe { &*self.slots.get() };
+            for cb in &slots[..count] {
+                cb(ctx);
+            }
+        }
+    }
+
+    static CRASH_CALLBACKS: CallbackRegistry = CallbackRegistry::new();
+    static TIMEOUT_CALLBACKS: CallbackRegistry = CallbackRegistry::new();
+
+    /// Register a callback to run (in signal-handler context) when a fatal
+    /// signal is caught. Call this before `install()`/`install_with_config`.
+    pub fn on_crash(cb: CrashCallback) {
+        CRASH_CALLBACKS.push(cb);
+    }
+
+    /// Register a callback to run (in signal-handler context) when the
+    /// timeout watchdog fires. Call this before `install_with_config`, and
+    /// set `Config::timeout` so there's a watchdog to fire at all.
+    ///
+    /// Registering your own callback(s) here replaces the default
+    /// (report-then-abort) behavior entirely — if none of your callbacks
+    /// terminate the process, it keeps running whatever wedged it in the
+    /// first place.
+    pub fn on_timeout(cb: CrashCallback) {
+        TIMEOUT_CALLBACKS.push(cb);
+    }
 
+    /// Format `value` as a decimal integer into a fixed-size stack buffer
+    /// and write it. No heap allocation, so it's safe from the handler.
+    fn write_dec(fd: libc::c_int, value: i64) {
+        let mut digits = [0u8; 20];
+        let mut n = value.unsigned_abs();
+        let mut i = digits.len();
+        loop {
+            i -= 1;
+            digits[i] = b'0' + (n % 10) as u8;
+            n /= 10;
+            if n == 0 {
+                break;
+            }
+        }
+        if value < 0 {
+            write_all(fd, b"-");
+        }
+        write_all(fd, &digits[i..]);
+    }
+
+    // `libc` only exposes `SEGV_MAPERR`/`SEGV_ACCERR` on a handful of
+    // targets (`hurd`, `aix`) — not Linux/macOS/BSD, where we actually run.
+    // These values are fixed by the kernel/glibc ABI (see
+    // `<bits/siginfo-consts.h>`), so define them locally instead.
+    const SEGV_MAPERR: libc::c_int = 1;
+    const SEGV_ACCERR: libc::c_int = 2;
+
+    /// Write a human-readable reason for a `si_code`, without allocating.
+    /// Only `SIGSEGV`'s `SEGV_MAPERR`/`SEGV_ACCERR` are spelled out;
+    /// everything else (including every `SIGBUS` code) falls back to
+    /// printing the raw integer.
+    fn write_si_code(fd: libc::c_int, sig: libc::c_int, code: libc::c_int) {
+        match (sig, code) {
+            (libc::SIGSEGV, SEGV_MAPERR) => write_all(fd, b"SEGV_MAPERR (address not mapped)"),
+            (libc::SIGSEGV, SEGV_ACCERR) => {
+                write_all(fd, b"SEGV_ACCERR (invalid permissions for mapped object)")
+            }
+            _ => {
+                write_all(fd, b"code ");
+                write_dec(fd, code as i64);
+            }
+        }
+    }
+
+    /// Build the (non-signal-time) banner text for a given signal. This is
+    /// plain old allocating Rust — it only ever runs from `install_with_config`.
+    fn build_banner(sig: libc::c_int) -> Vec<u8> {
+        let pid = std::process::id();
+        format!(
+            "\n╔══════════════════════════════════════════════════════════╗\n\
+             ║  CAUGHT {name} - Process paused for debugger attachment\n\
+             ╠══════════════════════════════════════════════════════════╣\n\
+             ║  PID: {pid}\n",
+            name = sig_name(sig),
+        )
+        .into_bytes()
+    }
+
+    /// The part of the banner that only depends on PID (and, in `gdbstub`
+    /// mode, the port), printed after the dynamic fault-address/PC lines.
+    fn build_footer(gdbstub_port: Option<u16>) -> Vec<u8> {
+        if let Some(port) = gdbstub_port {
+            return format!(
+                "║                                                          ║\n\
+                 ║  Waiting for a debugger on 127.0.0.1:{port}...\n\
+                 ║  Attach with:                                            ║\n\
+                 ║    target remote :{port}\n\
+                 ╠══════════════════════════════════════════════════════════╣\n"
+            )
+            .into_bytes();
+        }
+        let pid = std::process::id();
+        format!(
+            "║                                                          ║\n\
+             ║  Attach debugger:                                        ║\n\
+             ║    lldb -p {pid}\n\
+             ║    gdb -p {pid}\n\
+             ║                                                          ║\n\
+             ║  Then in debugger:                                       ║\n\
+             ║    bt          # backtrace                               ║\n\
+             ║    f 0         # select frame                            ║\n\
+             ║    di -p       # disassemble at PC                       ║\n\
+             ║    reg read    # show registers                          ║\n\
+             ╠══════════════════════════════════════════════════════════╣\n"
+        )
+        .into_bytes()
+    }
+
+    static FOOTER: OnceLock<Vec<u8>> = OnceLock::new();
+    // Non-ASCII box-drawing glyphs in a `b"..."` literal don't compile —
+    // byte strings are ASCII-only, so these are spelled out as raw UTF-8
+    // bytes (`\xe2\x95\x91` = '║', `\xe2\x95\x9a` = '╚', `\xe2\x95\x90` = '═',
+    // `\xe2\x95\x9d` = '╝'), matching the rest of this module.
+    const PAUSE_PROMPT: &[u8] =
+        b"\xe2\x95\x91\x20\x20\x50\x72\x65\x73\x73\x20\x45\x6e\x74\x65\x72\x20\x74\x6f\x20\x63\x6f\x6e\x74\x69\x6e\x75\x65\x20\x28\x77\x69\x6c\x6c\x20\x63\x72\x61\x73\x68\x29\x2e\x2e\x2e\x20\x20\x20\x20\x20\x20\x20\x20\x20\x20\x20\x20\x20\x20\x20\x20\x20\xe2\x95\x91\n\
+          \xe2\x95\x9a\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x9d\n";
+    const NO_PAUSE_FOOTER: &[u8] =
+        b"\xe2\x95\x9a\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x90\xe2\x95\x9d\n";
+
+    #[cfg(feature = "gdbstub")]
+    const NO_GDBSTUB_PORT: u32 = u32::MAX;
+    #[cfg(feature = "gdbstub")]
+    static GDBSTUB_PORT: std::sync::atomic::AtomicU32 =
+        std::sync::atomic::AtomicU32::new(NO_GDBSTUB_PORT);
+
+    #[cfg(feature = "gdbstub")]
+    fn gdbstub_port() -> Option<u16> {
+        match GDBSTUB_PORT.load(Ordering::SeqCst) {
+            NO_GDBSTUB_PORT => None,
+            port => Some(port as u16),
+        }
+    }
+    #[cfg(not(feature = "gdbstub"))]
+    fn gdbstub_port() -> Option<u16> {
+        None
+    }
+
+    /// The actual `TcpListener::accept`/`GdbStub::run_blocking` work
+    /// allocates and takes internal locks, so it can't run inside the
+    /// signal handler without risking the same deadlock-on-crash bug that
+    /// the alternate signal stack and `on_crash` callbacks were built to
+    /// avoid. Instead the handler only ever writes a [`CrashRegs`] snapshot
+    /// in here and wakes `GDB_WAKE_WRITE_FD`; a background thread spawned
+    /// by `install_with_config` does the actual serving and writes to the
+    /// "done" pipe when it's finished, whose read end (`GDB_DONE_READ_FD`)
+    /// the handler blocks on with a plain `read(2)` (signal-safe, unlike
+    /// locking or allocating).
+    #[cfg(feature = "gdbstub")]
+    struct GdbHandoff {
+        regs: CrashRegs,
+        sig: libc::c_int,
+    }
+
+    #[cfg(feature = "gdbstub")]
+    unsafe impl Sync for GdbHandoffCell {}
+
+    #[cfg(feature = "gdbstub")]
+    struct GdbHandoffCell(std::cell::UnsafeCell<Option<GdbHandoff>>);
+
+    #[cfg(feature = "gdbstub")]
+    static GDB_HANDOFF: GdbHandoffCell = GdbHandoffCell(std::cell::UnsafeCell::new(None));
+
+    #[cfg(feature = "gdbstub")]
+    static GDB_WAKE_WRITE_FD: std::sync::atomic::AtomicI32 = std::sync::atomic::AtomicI32::new(-1);
+    #[cfg(feature = "gdbstub")]
+    static GDB_DONE_READ_FD: std::sync::atomic::AtomicI32 = std::sync::atomic::AtomicI32::new(-1);
+
+    /// Block until a single byte is available on `fd`, retrying on
+    /// `EINTR`. Signal-safe: just a raw blocking `read(2)`.
+    fn block_on_byte(fd: libc::c_int) {
         let mut buf = [0u8; 1];
-        let _ = std::io::stdin().read(&mut buf);
+        loop {
+            let n = unsafe { libc::read(fd, buf.as_mut_ptr() as *mut libc::c_void, 1) };
+            if n >= 0 || unsafe { *libc::__errno_location() } != libc::EINTR {
+                break;
+            }
+        }
+    }
+
+    /// Spawn the background thread that serves gdbstub sessions outside
+    /// signal-handler context, and wire up the wake/done self-pipes the
+    /// handler uses to hand off to it. Only called when a gdbstub port is
+    /// configured.
+    #[cfg(feature = "gdbstub")]
+    fn spawn_gdb_server_thread(port: u16) {
+        let mut wake_fds = [0 as libc::c_int; 2];
+        let mut done_fds = [0 as libc::c_int; 2];
+        unsafe {
+            libc::pipe(wake_fds.as_mut_ptr());
+            libc::pipe(done_fds.as_mut_ptr());
+        }
+        let [wake_read, wake_write] = wake_fds;
+        let [done_read, done_write] = done_fds;
+        GDB_WAKE_WRITE_FD.store(wake_write, Ordering::SeqCst);
+        GDB_DONE_READ_FD.store(done_read, Ordering::SeqCst);
+
+        std::thread::spawn(move || loop {
+            block_on_byte(wake_read);
+            // SAFETY: the handler only ever writes this before waking us,
+            // and never touches it again until the next crash (which
+            // can't happen until we've written `done_write` below and the
+            // handler has unblocked).
+            let handoff = unsafe { (*GDB_HANDOFF.0.get()).take() };
+            if let Some(GdbHandoff { regs, sig }) = handoff {
+                gdb_target::serve(port, regs, sig);
+            }
+            write_all(done_write, b"\x01");
+        });
+    }
+
+    /// Write a full buffer to a raw fd, retrying on `EINTR`. Signal-safe:
+    /// no allocation, no libc calls beyond `write(2)`.
+    fn write_all(fd: libc::c_int, mut buf: &[u8]) {
+        while !buf.is_empty() {
+            let n = unsafe { libc::write(fd, buf.as_ptr() as *const libc::c_void, buf.len()) };
+            if n <= 0 {
+                if n < 0 && unsafe { *libc::__errno_location() } == libc::EINTR {
+                    continue;
+                }
+                return;
+            }
+            buf = &buf[n as usize..];
+        }
+    }
+
+    /// Format `value` as `0x...` into a fixed-size stack buffer and write it.
+    /// No heap allocation, so it's safe to call from the handler.
+    fn write_hex(fd: libc::c_int, value: u64) {
+        const DIGITS: &[u8; 16] = b"0123456789abcdef";
+        let mut buf = [0u8; 18]; // "0x" + 16 hex digits
+        buf[0] = b'0';
+        buf[1] = b'x';
+        for i in 0..16 {
+            let shift = (15 - i) * 4;
+            buf[2 + i] = DIGITS[((value >> shift) & 0xf) as usize];
+        }
+        write_all(fd, &buf);
+    }
+
+    /// Snapshot of the crashing thread's general-purpose registers, captured
+    /// from the `ucontext_t` while still inside the handler. `gdb_target`
+    /// serves these (and `/proc/self/mem`) to a remote debugger instead of
+    /// the thread's live state, since by the time anyone reads them the
+    /// handler may be running on the alternate signal stack.
+    #[cfg(feature = "gdbstub")]
+    #[derive(Clone, Copy, Default)]
+    struct CrashRegs {
+        #[cfg(target_arch = "x86_64")]
+        gregs: [u64; 23],
+        #[cfg(target_arch = "aarch64")]
+        gregs: [u64; 31],
+        #[cfg(target_arch = "aarch64")]
+        sp: u64,
+        pc: u64,
+    }
+
+    #[cfg(feature = "gdbstub")]
+    unsafe fn capture_regs(ctx: *mut libc::c_void) -> Option<CrashRegs> {
+        if ctx.is_null() {
+            return None;
+        }
+        let ctx = ctx as *mut libc::ucontext_t;
+        let pc = pc_from_ucontext(ctx as *mut libc::c_void)?;
+
+        #[cfg(target_arch = "x86_64")]
+        {
+            // Plain element-wise copy, not `.collect::<Vec<_>>()` — this
+            // runs inside the handler, where heap allocation isn't safe.
+            let mut gregs = [0u64; 23];
+            let src = &(*ctx).uc_mcontext.gregs;
+            for i in 0..23 {
+                gregs[i] = src[i] as u64;
+            }
+            Some(CrashRegs { gregs, pc })
+        }
+        #[cfg(target_arch = "aarch64")]
+        {
+            let mc = &(*ctx).uc_mcontext;
+            let mut gregs = [0u64; 31];
+            gregs.copy_from_slice(&mc.regs);
+            Some(CrashRegs { gregs, sp: mc.sp, pc })
+        }
+        #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+        {
+            None
+        }
+    }
+
+    #[cfg(all(feature = "gdbstub", target_arch = "x86_64"))]
+    impl CrashRegs {
+        fn copy_into(&self, regs: &mut gdbstub_arch::x86::reg::X86_64CoreRegs) {
+            regs.regs = [
+                self.gregs[libc::REG_RAX as usize],
+                self.gregs[libc::REG_RBX as usize],
+                self.gregs[libc::REG_RCX as usize],
+                self.gregs[libc::REG_RDX as usize],
+                self.gregs[libc::REG_RSI as usize],
+                self.gregs[libc::REG_RDI as usize],
+                self.gregs[libc::REG_RBP as usize],
+                self.gregs[libc::REG_RSP as usize],
+                self.gregs[libc::REG_R8 as usize],
+                self.gregs[libc::REG_R9 as usize],
+                self.gregs[libc::REG_R10 as usize],
+                self.gregs[libc::REG_R11 as usize],
+                self.gregs[libc::REG_R12 as usize],
+                self.gregs[libc::REG_R13 as usize],
+                self.gregs[libc::REG_R14 as usize],
+                self.gregs[libc::REG_R15 as usize],
+            ];
+            regs.rip = self.pc;
+        }
+    }
+
+    #[cfg(all(feature = "gdbstub", target_arch = "aarch64"))]
+    impl CrashRegs {
+        fn copy_into(&self, regs: &mut gdbstub_arch::aarch64::reg::AArch64CoreRegs) {
+            regs.x = self.gregs;
+            regs.sp = self.sp;
+            regs.pc = self.pc;
+        }
+    }
+
+    /// In-process GDB Remote Serial Protocol server for the crashing thread.
+    ///
+    /// Exposes the register snapshot taken by [`capture_regs`] and reads
+    /// memory straight out of `/proc/self/mem`, so `gdb`/`lldb` can connect
+    /// over TCP and inspect a process that has no attachable stdin — the
+    /// common case for CI and containers. Single-shot and read-only: it
+    /// reports one stop reason (the caught signal) and never resumes the
+    /// crashed thread, since there is nothing sane to resume it into.
+    #[cfg(feature = "gdbstub")]
+    mod gdb_target {
+        use super::CrashRegs;
+        use gdbstub::common::Signal;
+        use gdbstub::conn::{Connection, ConnectionExt};
+        use gdbstub::stub::{run_blocking, GdbStub, SingleThreadStopReason};
+        use gdbstub::target::ext::base::singlethread::{SingleThreadBase, SingleThreadResumeOps};
+        use gdbstub::target::ext::base::BaseOps;
+        use gdbstub::target::{Target, TargetError, TargetResult};
+        use std::fs::File;
+        use std::io::{Read, Seek, SeekFrom};
+        use std::net::TcpListener;
+
+        #[cfg(target_arch = "x86_64")]
+        type Arch = gdbstub_arch::x86::X86_64_SSE;
+        #[cfg(target_arch = "aarch64")]
+        type Arch = gdbstub_arch::aarch64::AArch64;
+
+        /// The crashing thread, frozen in time: registers come from the
+        /// snapshot taken inside the signal handler, memory comes straight
+        /// from `/proc/self/mem`. There is no live execution behind this,
+        /// so every write/resume request is rejected.
+        pub struct CrashTarget {
+            regs: CrashRegs,
+            sig: Signal,
+        }
+
+        impl Target for CrashTarget {
+            type Arch = Arch;
+            type Error = &'static str;
+
+            fn base_ops(&mut self) -> BaseOps<'_, Self::Arch, Self::Error> {
+                BaseOps::SingleThread(self)
+            }
+
+            // `CrashTarget` doesn't implement breakpoints at all, so
+            // gdbstub's default guard rail (which exists to stop a target
+            // claiming sw-breakpoint support without backing it) refuses
+            // to run the state machine and `run_blocking` fails before a
+            // single packet is exchanged. Since this target is read-only
+            // and never resumes, there's nothing the guard rail is
+            // protecting here.
+            fn guard_rail_implicit_sw_breakpoints(&self) -> bool {
+                true
+            }
+        }
+
+        impl SingleThreadBase for CrashTarget {
+            fn read_registers(
+                &mut self,
+                regs: &mut <Self::Arch as gdbstub::arch::Arch>::Registers,
+            ) -> TargetResult<(), Self> {
+                self.regs.copy_into(regs);
+                Ok(())
+            }
+
+            fn write_registers(
+                &mut self,
+                _regs: &<Self::Arch as gdbstub::arch::Arch>::Registers,
+            ) -> TargetResult<(), Self> {
+                Err(TargetError::NonFatal)
+            }
+
+            fn read_addrs(
+                &mut self,
+                start_addr: <Self::Arch as gdbstub::arch::Arch>::Usize,
+                data: &mut [u8],
+            ) -> TargetResult<usize, Self> {
+                let Ok(mut mem) = File::open("/proc/self/mem") else {
+                    return Err(TargetError::NonFatal);
+                };
+                if mem.seek(SeekFrom::Start(start_addr)).is_err() {
+                    return Err(TargetError::NonFatal);
+                }
+                Ok(mem.read(data).unwrap_or(0))
+            }
+
+            fn write_addrs(
+                &mut self,
+                _start_addr: <Self::Arch as gdbstub::arch::Arch>::Usize,
+                _data: &[u8],
+            ) -> TargetResult<(), Self> {
+                Err(TargetError::NonFatal)
+            }
+
+            fn support_resume(&mut self) -> Option<SingleThreadResumeOps<'_, Self>> {
+                // No live thread to resume; report everything over `?`/`g`/`m` only.
+                None
+            }
+        }
+
+        /// There's no running target to wait on: the moment a debugger
+        /// connects, we already know the one stop reason we'll ever report.
+        struct CrashEventLoop;
+
+        impl run_blocking::BlockingEventLoop for CrashEventLoop {
+            type Target = CrashTarget;
+            type Connection = Box<dyn ConnectionExt<Error = std::io::Error>>;
+            type StopReason = SingleThreadStopReason<u64>;
+
+            fn wait_for_stop_reason(
+                target: &mut CrashTarget,
+                _conn: &mut Self::Connection,
+            ) -> Result<
+                run_blocking::Event<Self::StopReason>,
+                run_blocking::WaitForStopReasonError<
+                    <Self::Target as Target>::Error,
+                    <Self::Connection as Connection>::Error,
+                >,
+            > {
+                Ok(run_blocking::Event::TargetStopped(
+                    SingleThreadStopReason::Signal(target.sig),
+                ))
+            }
+
+            fn on_interrupt(
+                _target: &mut CrashTarget,
+            ) -> Result<Option<Self::StopReason>, <Self::Target as Target>::Error> {
+                Ok(None)
+            }
+        }
+
+        /// Block until a debugger connects to `127.0.0.1:<port>` and serve
+        /// its session, reporting `sig` as the single stop reason.
+        pub fn serve(port: u16, regs: CrashRegs, sig: libc::c_int) {
+            let Ok(listener) = TcpListener::bind(("127.0.0.1", port)) else {
+                return;
+            };
+            let Ok((stream, _)) = listener.accept() else {
+                return;
+            };
+            let connection: Box<dyn ConnectionExt<Error = std::io::Error>> = Box::new(stream);
+
+            let mut target = CrashTarget {
+                regs,
+                sig: Signal(sig as u8),
+            };
+            if let Err(e) = GdbStub::new(connection).run_blocking::<CrashEventLoop>(&mut target) {
+                super::write_all(2, b"[sigsegv_handler] gdbstub session failed: ");
+                super::write_all(2, format!("{e:?}").as_bytes());
+                super::write_all(2, b"\n");
+            }
+        }
+    }
+
+    /// Raw stack-walk capture, kept separate from symbol resolution.
+    ///
+    /// Walking frames with the `backtrace` crate's `trace_unsynchronized`
+    /// only reads instruction pointers off the stack, which is about as
+    /// cheap as async-signal-safety gets; resolving those addresses to
+    /// function names allocates and can hit the filesystem (for debug
+    /// info), so that part is deferred until we're past the handler's
+    /// pause-for-debugger step. For JIT crashes most of these addresses
+    /// won't resolve to a symbol anyway — what you want there is the raw
+    /// address plus its offset from the containing module, to match
+    /// against the JIT's own code map.
+    #[cfg(feature = "backtrace")]
+    mod frames {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        const MAX_FRAMES: usize = 128;
+
+        #[derive(Clone, Copy)]
+        struct Frame {
+            ip: usize,
+            module_base: usize,
+        }
+
+        static FRAME_COUNT: AtomicUsize = AtomicUsize::new(0);
+        static mut FRAMES: [Frame; MAX_FRAMES] = [Frame {
+            ip: 0,
+            module_base: 0,
+        }; MAX_FRAMES];
+
+        /// Walk the crashing thread's frames into a fixed-size static
+        /// buffer. Must be called from the signal handler itself — this is
+        /// the only part of backtrace capture that runs there.
+        pub fn capture() {
+            let mut i = 0;
+            // SAFETY: `trace_unsynchronized` is unsafe because the caller
+            // must not let the passed closure unwind, allocate, or access
+            // the backtrace after this call returns — none of which we do
+            // here; we only stash raw instruction pointers into a static
+            // array, and only one crash is ever in flight at a time (see
+            // `CAUGHT`).
+            unsafe {
+                backtrace::trace_unsynchronized(|frame| {
+                    if i >= MAX_FRAMES {
+                        return false;
+                    }
+                    FRAMES[i] = Frame {
+                        ip: frame.ip() as usize,
+                        module_base: frame.module_base_address().map_or(0, |p| p as usize),
+                    };
+                    i += 1;
+                    true
+                });
+            }
+            FRAME_COUNT.store(i, Ordering::SeqCst);
+        }
+
+        /// Print the raw addresses captured by `capture`, with each
+        /// frame's offset from its module base when known. Signal-safe:
+        /// only hex formatting and `write(2)`.
+        pub fn print_raw(fd: libc::c_int) {
+            super::write_all(fd, b"\xe2\x95\x91  Backtrace (raw, unsymbolicated):\n");
+            let count = FRAME_COUNT.load(Ordering::SeqCst);
+            for frame in unsafe { &FRAMES[..count] } {
+                super::write_all(fd, b"\xe2\x95\x91    ");
+                super::write_hex(fd, frame.ip as u64);
+                if frame.module_base != 0 {
+                    super::write_all(fd, b"  (module+");
+                    super::write_hex(fd, (frame.ip - frame.module_base) as u64);
+                    super::write_all(fd, b")");
+                }
+                super::write_all(fd, b"\n");
+            }
+        }
+
+        /// Symbolicate the frames captured by `capture` and print them.
+        /// Not signal-safe (allocates, may read debug info from disk) —
+        /// only call this once we're past the handler's safety-critical
+        /// section, e.g. after the user has pressed Enter.
+        pub fn print_symbolicated() {
+            let count = FRAME_COUNT.load(Ordering::SeqCst);
+            eprintln!("Symbolicated backtrace:");
+            for (n, frame) in unsafe { &FRAMES[..count] }.iter().enumerate() {
+                let mut found = false;
+                backtrace::resolve(frame.ip as *mut libc::c_void, |symbol| {
+                    found = true;
+                    eprintln!(
+                        "  {n:>3}: {:#x} - {}",
+                        frame.ip,
+                        symbol
+                            .name()
+                            .map(|n| n.to_string())
+                            .unwrap_or_else(|| "<unknown>".to_string())
+                    );
+                });
+                if !found {
+                    eprintln!("  {n:>3}: {:#x} - <no symbol>", frame.ip);
+                }
+            }
+        }
+    }
+
+    /// Decode the crashing instruction pointer from the platform-specific
+    /// `ucontext_t` that the kernel hands to a `SA_SIGINFO` handler.
+    ///
+    /// Returns `None` on architectures we don't know how to decode; the
+    /// banner just omits the PC line in that case.
+    unsafe fn pc_from_ucontext(ctx: *mut libc::c_void) -> Option<u64> {
+        if ctx.is_null() {
+            return None;
+        }
+        let ctx = ctx as *mut libc::ucontext_t;
+
+        #[cfg(target_arch = "x86_64")]
+        {
+            Some((*ctx).uc_mcontext.gregs[libc::REG_RIP as usize] as u64)
+        }
+
+        #[cfg(target_arch = "aarch64")]
+        {
+            Some((*ctx).uc_mcontext.pc as u64)
+        }
+
+        #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+        {
+            let _ = ctx;
+            None
+        }
+    }
+
+    /// The default callback, registered automatically when nobody calls
+    /// `on_crash`/`on_timeout` themselves, reproducing this module's
+    /// original pause-and-print behavior: dump the banner, fault info,
+    /// backtrace and PC, then either hand off to gdbstub or block on
+    /// stdin.
+    fn default_pause_callback(ctx: &CrashContext) {
+        let banner = if ctx.is_timeout {
+            ALARM_BANNER.get()
+        } else {
+            BANNERS.get().map(|banners| &banners[sig_index(ctx.signal)])
+        };
+        if let Some(banner) = banner {
+            write_all(2, banner);
+        }
+        if let Some(addr) = ctx.fault_addr {
+            write_all(2, b"\xe2\x95\x91  Fault address: ");
+            write_hex(2, addr);
+            write_all(2, b"\n");
+        }
+        if let Some(code) = ctx.si_code {
+            write_all(2, b"\xe2\x95\x91  Reason: ");
+            write_si_code(2, ctx.signal, code);
+            write_all(2, b"\n");
+        }
+        if let Some(pc) = ctx.pc {
+            write_all(2, b"\xe2\x95\x91  Crashing PC:    ");
+            write_hex(2, pc);
+            write_all(2, b"\n");
+        }
+        #[cfg(feature = "backtrace")]
+        frames::print_raw(2);
+
+        if let Some(footer) = FOOTER.get() {
+            write_all(2, footer);
+        }
+
+        if let Some(_port) = gdbstub_port() {
+            #[cfg(feature = "gdbstub")]
+            if let Some(regs) = unsafe { capture_regs(ctx.ucontext) } {
+                // SAFETY: single-writer — only one crash is ever live at a
+                // time (see `CAUGHT`) — and the background thread doesn't
+                // read this until it's woken below.
+                unsafe {
+                    *GDB_HANDOFF.0.get() = Some(GdbHandoff {
+                        regs,
+                        sig: ctx.signal,
+                    });
+                }
+                let wake_fd = GDB_WAKE_WRITE_FD.load(Ordering::SeqCst);
+                write_all(wake_fd, b"\x01");
+                // Block (signal-safe: plain `read(2)`) until the
+                // background thread has actually served the session —
+                // the TCP accept/`run_blocking` work itself happens off
+                // this thread, where allocating and locking is safe.
+                block_on_byte(GDB_DONE_READ_FD.load(Ordering::SeqCst));
+            }
+        } else if PAUSE_FOR_DEBUGGER.load(Ordering::SeqCst) {
+            write_all(2, PAUSE_PROMPT);
+            block_on_byte(0);
+        } else {
+            write_all(2, NO_PAUSE_FOOTER);
+        }
+
+        // Symbol resolution allocates and isn't async-signal-safe; it's
+        // fine here because the safety-critical window above has already
+        // closed — we're either about to crash again (pause path) or the
+        // gdbstub session has already ended, not racing another signal
+        // delivery.
+        #[cfg(feature = "backtrace")]
+        frames::print_symbolicated();
+
+        // Unlike a real fault, returning from a timeout handler drops
+        // straight back into the wedged code — there's no faulting
+        // instruction to re-trip the crash path, so the thread would just
+        // keep spinning. Terminate here instead of handing back control;
+        // `abort()` is async-signal-safe, same as the recursion guard in
+        // `handler` below.
+        if ctx.is_timeout {
+            std::process::abort();
+        }
+    }
+
+    /// Build a `CrashContext` from the kernel-supplied siginfo/ucontext and
+    /// capture a backtrace, if the `backtrace` feature is enabled.
+    ///
+    /// SAFETY: must only be called from within a signal handler, with the
+    /// `info`/`ctx` pointers the kernel handed to it.
+    unsafe fn build_context(
+        sig: libc::c_int,
+        info: *mut libc::siginfo_t,
+        ctx: *mut libc::c_void,
+        is_timeout: bool,
+    ) -> CrashContext {
+        let (fault_addr, si_code) = if info.is_null() {
+            (None, None)
+        } else {
+            (Some((*info).si_addr() as u64), Some((*info).si_code))
+        };
+        let pc = pc_from_ucontext(ctx);
+
+        #[cfg(feature = "backtrace")]
+        frames::capture();
+
+        CrashContext {
+            signal: sig,
+            is_timeout,
+            fault_addr,
+            si_code,
+            pc,
+            ucontext: ctx,
+        }
+    }
+
+    extern "C" fn handler(sig: libc::c_int, info: *mut libc::siginfo_t, ctx: *mut libc::c_void) {
+        // Prevent recursive signals
+        if CAUGHT.swap(true, Ordering::SeqCst) {
+            std::process::abort();
+        }
+
+        // SAFETY: called by the kernel as our registered `sigaction` handler.
+        let context = unsafe { build_context(sig, info, ctx, false) };
+        CRASH_CALLBACKS.run(&context);
+    }
+
+    extern "C" fn alarm_handler(
+        sig: libc::c_int,
+        info: *mut libc::siginfo_t,
+        ctx: *mut libc::c_void,
+    ) {
+        // Same recursion/overlap guard as `handler`: a crash and a timeout
+        // are otherwise unrelated signals, so without sharing `CAUGHT`
+        // here the two could run concurrently (e.g. a SIGALRM arriving
+        // while `handler` is parked in `block_on_byte`) and race on
+        // `FRAMES`/`GDB_HANDOFF`, which assume a single report in flight.
+        // `install_with_config` also blocks each of these signals while
+        // any of the others' handlers run, via `sa_mask`, as a second line
+        // of defense.
+        if CAUGHT.swap(true, Ordering::SeqCst) {
+            std::process::abort();
+        }
+
+        // SAFETY: called by the kernel as our registered `sigaction` handler.
+        let context = unsafe { build_context(sig, info, ctx, true) };
+        TIMEOUT_CALLBACKS.run(&context);
+    }
+
+    /// Configuration for [`install_with_config`].
+    pub struct Config {
+        /// Size in bytes of the alternate signal stack used to run the
+        /// handler, so it still fires when the crash *is* a stack overflow.
+        pub altstack_size: usize,
+        /// Whether the handler should block on stdin before returning
+        /// (letting you attach a debugger first). Defaults to `true`.
+        /// Ignored when `gdbstub_port` is set.
+        pub pause_for_debugger: bool,
+        /// When set (and built with the `gdbstub` feature), the handler
+        /// opens an in-process GDB Remote Serial Protocol server on
+        /// `127.0.0.1:<port>` instead of waiting on stdin, and prints
+        /// `target remote :<port>` for you to paste into gdb or lldb.
+        #[cfg(feature = "gdbstub")]
+        pub gdbstub_port: Option<u16>,
+        /// Wall-clock budget for the test. If it elapses before the
+        /// process exits, a `SIGALRM` fires and the timeout callbacks run
+        /// — useful for a JIT test that wedges in an infinite loop rather
+        /// than crashing outright. The default timeout callback reports
+        /// and then aborts the process, since returning would just drop
+        /// back into the wedged code; a custom `on_timeout` callback that
+        /// doesn't itself terminate the process will leave it running.
+        pub timeout: Option<std::time::Duration>,
+    }
+
+    impl Default for Config {
+        fn default() -> Self {
+            Self {
+                altstack_size: 1 << 16,
+                pause_for_debugger: true,
+                #[cfg(feature = "gdbstub")]
+                gdbstub_port: None,
+                timeout: None,
+            }
+        }
     }
 
     /// Install signal handlers for common crash signals.
     /// Call this at the start of your test.
     pub fn install() {
+        install_with_config(Config::default());
+    }
+
+    /// Like [`install`], but lets you size the alternate signal stack,
+    /// control whether the handler pauses for a debugger, and configure a
+    /// timeout watchdog or gdbstub port.
+    pub fn install_with_config(config: Config) {
+        PAUSE_FOR_DEBUGGER.store(config.pause_for_debugger, Ordering::SeqCst);
+        #[cfg(feature = "gdbstub")]
+        if let Some(port) = config.gdbstub_port {
+            GDBSTUB_PORT.store(port as u32, Ordering::SeqCst);
+            spawn_gdb_server_thread(port);
+        }
+
+        // Nobody registered their own callbacks, so wire up the
+        // pause-and-print behavior this module has always had — existing
+        // callers of `install()` see no change.
+        if CRASH_CALLBACKS.is_empty() {
+            on_crash(default_pause_callback);
+        }
+        if TIMEOUT_CALLBACKS.is_empty() {
+            on_timeout(default_pause_callback);
+        }
+
+        let banners: [Vec<u8>; 4] = std::array::from_fn(|i| build_banner(SIGNALS[i]));
+        let _ = BANNERS.set(banners);
+        let _ = FOOTER.set(build_footer(gdbstub_port()));
+        if config.timeout.is_some() {
+            let _ = ALARM_BANNER.set(build_banner(libc::SIGALRM));
+        }
+
         unsafe {
-            libc::signal(libc::SIGSEGV, handler as usize);
-            libc::signal(libc::SIGBUS, handler as usize);
-            libc::signal(libc::SIGFPE, handler as usize);
-            libc::signal(libc::SIGILL, handler as usize);
+            // Run the handler on its own stack: if the crash is a stack
+            // overflow, the thread's normal stack is unusable.
+            let stack_mem = Box::leak(vec![0u8; config.altstack_size].into_boxed_slice());
+            let stack = libc::stack_t {
+                ss_sp: stack_mem.as_mut_ptr() as *mut libc::c_void,
+                ss_flags: 0,
+                ss_size: stack_mem.len(),
+            };
+            libc::sigaltstack(&stack, std::ptr::null_mut());
+
+            // Block every signal this module handles while any one of
+            // them is running — not just the one currently being
+            // delivered (the kernel does that much for us by default) —
+            // so `handler` and `alarm_handler` can never overlap on the
+            // same thread and race on the statics they share.
+            let mut blocked: libc::sigset_t = std::mem::zeroed();
+            libc::sigemptyset(&mut blocked);
+            for sig in SIGNALS {
+                libc::sigaddset(&mut blocked, sig);
+            }
+            libc::sigaddset(&mut blocked, libc::SIGALRM);
+
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = handler as *const () as usize;
+            action.sa_flags = libc::SA_SIGINFO | libc::SA_ONSTACK;
+            action.sa_mask = blocked;
+
+            for sig in SIGNALS {
+                libc::sigaction(sig, &action, std::ptr::null_mut());
+            }
+
+            if let Some(timeout) = config.timeout {
+                let mut alarm_action: libc::sigaction = std::mem::zeroed();
+                alarm_action.sa_sigaction = alarm_handler as *const () as usize;
+                alarm_action.sa_flags = libc::SA_SIGINFO | libc::SA_ONSTACK;
+                alarm_action.sa_mask = blocked;
+                libc::sigaction(libc::SIGALRM, &alarm_action, std::ptr::null_mut());
+
+                let timer = libc::itimerval {
+                    it_interval: libc::timeval {
+                        tv_sec: 0,
+                        tv_usec: 0,
+                    },
+                    it_value: libc::timeval {
+                        tv_sec: timeout.as_secs() as libc::time_t,
+                        tv_usec: timeout.subsec_micros() as libc::suseconds_t,
+                    },
+                };
+                libc::setitimer(libc::ITIMER_REAL, &timer, std::ptr::null_mut());
+            }
         }
         eprintln!("[sigsegv_handler] Installed crash handlers for PID {}", std::process::id());
     }
 }
 
-#[cfg(not(unix))]
+// Windows has no signals; the equivalent mechanism is a vectored exception
+// handler, which gets first look at structured exceptions (access
+// violations, illegal instructions, divide-by-zero, ...) process-wide,
+// before the OS's own unhandled-exception machinery runs.
+//
+// Usage: add `windows-sys = { version = "0.52", features = ["Win32_Foundation",
+// "Win32_System_Diagnostics_Debug", "Win32_System_Console",
+// "Win32_Storage_FileSystem", "Win32_System_IO", "Win32_System_Threading"] }`
+// to dev-dependencies.
+#[cfg(windows)]
 pub mod sigsegv_handler {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use windows_sys::Win32::Foundation::{
+        EXCEPTION_ACCESS_VIOLATION, EXCEPTION_CONTINUE_SEARCH, EXCEPTION_ILLEGAL_INSTRUCTION,
+        EXCEPTION_INT_DIVIDE_BY_ZERO, EXCEPTION_STACK_OVERFLOW, HANDLE,
+    };
+    use windows_sys::Win32::Storage::FileSystem::{ReadFile, WriteFile};
+    use windows_sys::Win32::System::Console::{GetStdHandle, STD_ERROR_HANDLE, STD_INPUT_HANDLE};
+    use windows_sys::Win32::System::Diagnostics::Debug::{
+        AddVectoredExceptionHandler, EXCEPTION_POINTERS,
+    };
+    use windows_sys::Win32::System::Threading::SetThreadStackGuarantee;
+
+    static CAUGHT: AtomicBool = AtomicBool::new(false);
+    static PAUSE_FOR_DEBUGGER: AtomicBool = AtomicBool::new(true);
+
+    /// The context handed to every registered callback. Mirrors the Unix
+    /// side's `CrashContext` where the concepts line up — there's no
+    /// exception-number equivalent of a timeout watchdog here, since this
+    /// module doesn't (yet) run one on Windows.
+    pub struct CrashContext {
+        /// The raw `ExceptionCode` from the `EXCEPTION_RECORD`.
+        pub exception_code: u32,
+        /// The faulting address, for `EXCEPTION_ACCESS_VIOLATION`.
+        pub fault_addr: Option<u64>,
+        /// The crashing thread's instruction pointer, when decodable on
+        /// this architecture.
+        pub pc: Option<u64>,
+    }
+
+    pub type CrashCallback = fn(&CrashContext);
+
+    /// Append-only list of callbacks. See the Unix side's
+    /// `CallbackRegistry` for why this is an `UnsafeCell` rather than a
+    /// `Mutex`: taking a lock from inside the exception handler risks
+    /// deadlocking against the very thread it interrupted.
+    struct CallbackRegistry {
+        slots: std::cell::UnsafeCell<Vec<CrashCallback>>,
+        count: AtomicUsize,
+    }
+
+    unsafe impl Sync for CallbackRegistry {}
+
+    impl CallbackRegistry {
+        const fn new() -> Self {
+            Self {
+                slots: std::cell::UnsafeCell::new(Vec::new()),
+                count: AtomicUsize::new(0),
+            }
+        }
+
+        fn push(&self, cb: CrashCallback) {
+            // SAFETY: only called during setup, before `install_with_config`
+            // registers the exception handler.
+            unsafe { (*self.slots.get()).push(cb) };
+            self.count.store(unsafe { (*self.slots.get()).len() }, Ordering::SeqCst);
+        }
+
+        fn is_empty(&self) -> bool {
+            self.count.load(Ordering::SeqCst) == 0
+        }
+
+        /// Run every registered callback in registration order. Safe to
+        /// call from the exception handler as long as every registered
+        /// callback is itself safe to run there.
+        fn run(&self, ctx: &CrashContext) {
+            let count = self.count.load(Ordering::SeqCst);
+            // SAFETY: read-only from here on; no concurrent `push` can
+            // happen once an exception is live to call `run`.
+            let slots = unsafe { &*self.slots.get() };
+            for cb in &slots[..count] {
+                cb(ctx);
+            }
+        }
+    }
+
+    static CRASH_CALLBACKS: CallbackRegistry = CallbackRegistry::new();
+
+    /// Register a callback to run (in exception-handler context) when a
+    /// structured exception is caught. Call this before
+    /// `install()`/`install_with_config`.
+    pub fn on_crash(cb: CrashCallback) {
+        CRASH_CALLBACKS.push(cb);
+    }
+
+    /// Write a full buffer via a raw `WriteFile`, retrying on a short
+    /// write. No allocation and none of the CRT's stdio buffering/locking
+    /// that `eprintln!` goes through — unlike `eprintln!`, safe to call
+    /// from inside the exception handler.
+    fn write_all(handle: HANDLE, mut buf: &[u8]) {
+        while !buf.is_empty() {
+            let mut written = 0u32;
+            let ok = unsafe {
+                WriteFile(
+                    handle,
+                    buf.as_ptr(),
+                    buf.len() as u32,
+                    &mut written,
+                    std::ptr::null_mut(),
+                )
+            };
+            if ok == 0 || written == 0 {
+                return;
+            }
+            buf = &buf[written as usize..];
+        }
+    }
+
+    /// Format `value` as `0x...` into a fixed-size stack buffer and write
+    /// it. No heap allocation, so it's safe to call from the handler.
+    fn write_hex(handle: HANDLE, value: u64) {
+        const DIGITS: &[u8; 16] = b"0123456789abcdef";
+        let mut buf = [0u8; 18]; // "0x" + 16 hex digits
+        buf[0] = b'0';
+        buf[1] = b'x';
+        for i in 0..16 {
+            let shift = (15 - i) * 4;
+            buf[2 + i] = DIGITS[((value >> shift) & 0xf) as usize];
+        }
+        write_all(handle, &buf);
+    }
+
+    /// Format `value` as a decimal integer into a fixed-size stack buffer
+    /// and write it. No heap allocation, so it's safe from the handler.
+    fn write_dec(handle: HANDLE, value: u32) {
+        let mut digits = [0u8; 10];
+        let mut n = value;
+        let mut i = digits.len();
+        loop {
+            i -= 1;
+            digits[i] = b'0' + (n % 10) as u8;
+            n /= 10;
+            if n == 0 {
+                break;
+            }
+        }
+        write_all(handle, &digits[i..]);
+    }
+
+    /// Block until a single byte is available on `handle`. Analogous to
+    /// the Unix side's `block_on_byte`: a raw `ReadFile`, not the CRT
+    /// stdin path `std::io::stdin()` goes through, which allocates and
+    /// takes a lock shared with every other reader of stdin.
+    fn block_on_byte(handle: HANDLE) {
+        let mut buf = [0u8; 1];
+        let mut read = 0u32;
+        unsafe {
+            ReadFile(handle, buf.as_mut_ptr(), 1, &mut read, std::ptr::null_mut());
+        }
+    }
+
+    fn exception_name(code: u32) -> &'static str {
+        match code {
+            EXCEPTION_ACCESS_VIOLATION => "EXCEPTION_ACCESS_VIOLATION",
+            EXCEPTION_ILLEGAL_INSTRUCTION => "EXCEPTION_ILLEGAL_INSTRUCTION",
+            EXCEPTION_INT_DIVIDE_BY_ZERO => "EXCEPTION_INT_DIVIDE_BY_ZERO",
+            EXCEPTION_STACK_OVERFLOW => "EXCEPTION_STACK_OVERFLOW",
+            _ => "UNKNOWN",
+        }
+    }
+
+    /// The default callback, registered automatically when nobody calls
+    /// `on_crash` themselves, giving the same pause-and-print behavior as
+    /// the Unix side's default — built entirely out of raw
+    /// `WriteFile`/`ReadFile` instead of `eprintln!`/`stdin().read()`,
+    /// which allocate and go through the CRT's buffered-stdio locks and
+    /// so aren't safe to run this deep inside exception-handler context
+    /// (the same reasoning that moved the Unix handler off of them).
+    fn default_pause_callback(ctx: &CrashContext) {
+        let stderr = unsafe { GetStdHandle(STD_ERROR_HANDLE) };
+        let pid = std::process::id();
+
+        write_all(stderr, b"\nCAUGHT ");
+        write_all(stderr, exception_name(ctx.exception_code).as_bytes());
+        write_all(stderr, b" - Process paused for debugger attachment\n");
+        write_all(stderr, b"PID: ");
+        write_dec(stderr, pid);
+        write_all(stderr, b"\n");
+        if let Some(addr) = ctx.fault_addr {
+            write_all(stderr, b"Fault address: ");
+            write_hex(stderr, addr);
+            write_all(stderr, b"\n");
+        }
+        if let Some(pc) = ctx.pc {
+            write_all(stderr, b"Crashing PC: ");
+            write_hex(stderr, pc);
+            write_all(stderr, b"\n");
+        }
+        write_all(stderr, b"Attach debugger:\n  cdb -p ");
+        write_dec(stderr, pid);
+        write_all(stderr, b"\n  windbg -p ");
+        write_dec(stderr, pid);
+        write_all(stderr, b"\n");
+
+        if PAUSE_FOR_DEBUGGER.load(Ordering::SeqCst) {
+            write_all(stderr, b"Press Enter to continue (will crash)...\n");
+            block_on_byte(unsafe { GetStdHandle(STD_INPUT_HANDLE) });
+        }
+    }
+
+    /// Vectored exception handler, registered process-wide by `install`.
+    /// Returning `EXCEPTION_CONTINUE_SEARCH` lets the exception continue
+    /// down the normal chain (eventually crashing the process) once we're
+    /// done reporting it — we're here to inform the attach, not to
+    /// recover.
+    unsafe extern "system" fn vectored_handler(exception_info: *mut EXCEPTION_POINTERS) -> i32 {
+        let record = &*(*exception_info).ExceptionRecord;
+        let code = record.ExceptionCode as u32;
+        if !matches!(
+            code,
+            EXCEPTION_ACCESS_VIOLATION
+                | EXCEPTION_ILLEGAL_INSTRUCTION
+                | EXCEPTION_INT_DIVIDE_BY_ZERO
+                | EXCEPTION_STACK_OVERFLOW
+        ) {
+            return EXCEPTION_CONTINUE_SEARCH;
+        }
+        // Prevent recursive reporting if the crash handler itself faults.
+        if CAUGHT.swap(true, Ordering::SeqCst) {
+            return EXCEPTION_CONTINUE_SEARCH;
+        }
+
+        // `ExceptionInformation[1]` is the faulting address for access
+        // violations; see the `EXCEPTION_RECORD` docs.
+        let fault_addr = (code == EXCEPTION_ACCESS_VIOLATION && record.NumberParameters >= 2)
+            .then_some(record.ExceptionInformation[1] as u64);
+        #[cfg(target_arch = "x86_64")]
+        let pc = Some((*(*exception_info).ContextRecord).Rip);
+        #[cfg(target_arch = "x86")]
+        let pc = Some((*(*exception_info).ContextRecord).Eip as u64);
+        #[cfg(not(any(target_arch = "x86_64", target_arch = "x86")))]
+        let pc: Option<u64> = None;
+
+        let context = CrashContext {
+            exception_code: code,
+            fault_addr,
+            pc,
+        };
+        CRASH_CALLBACKS.run(&context);
+
+        EXCEPTION_CONTINUE_SEARCH
+    }
+
+    /// Configuration for [`install_with_config`].
+    pub struct Config {
+        /// Whether the handler should block on stdin before returning
+        /// (letting you attach a debugger first). Defaults to `true`.
+        pub pause_for_debugger: bool,
+        /// Extra stack, in bytes, reserved via `SetThreadStackGuarantee`
+        /// for the handler to run on when the exception *is*
+        /// `EXCEPTION_STACK_OVERFLOW` — without this there's a real risk
+        /// of no stack being left to run `vectored_handler` at all.
+        /// `SetThreadStackGuarantee` only affects the thread that calls
+        /// it and only takes effect for stack growth that hasn't happened
+        /// yet, so call `install`/`install_with_config` near the start of
+        /// every thread you expect to crash on, not just the main one.
+        /// Defaults to 64 KiB.
+        pub stack_overflow_guarantee: u32,
+    }
+
+    impl Default for Config {
+        fn default() -> Self {
+            Self {
+                pause_for_debugger: true,
+                stack_overflow_guarantee: 1 << 16,
+            }
+        }
+    }
+
+    /// Install a vectored exception handler for common crash signals.
+    /// Call this at the start of your test.
     pub fn install() {
-        eprintln!("[sigsegv_handler] Not available on this platform, use cdb/WinDbg instead");
+        install_with_config(Config::default());
+    }
+
+    /// Like [`install`], but lets you control whether the handler pauses
+    /// for a debugger and how much stack headroom it reserves for a
+    /// stack-overflow exception.
+    pub fn install_with_config(config: Config) {
+        PAUSE_FOR_DEBUGGER.store(config.pause_for_debugger, Ordering::SeqCst);
+
+        // Nobody registered their own callback, so wire up the same
+        // pause-and-print behavior the Unix side uses by default.
+        if CRASH_CALLBACKS.is_empty() {
+            on_crash(default_pause_callback);
+        }
+
+        unsafe {
+            // Reserve extra stack so `EXCEPTION_STACK_OVERFLOW` has room
+            // left to run `vectored_handler` on.
+            let mut guarantee = config.stack_overflow_guarantee;
+            SetThreadStackGuarantee(&mut guarantee);
+
+            // `1` runs our handler before any handler already registered.
+            AddVectoredExceptionHandler(1, Some(vectored_handler));
+        }
+        eprintln!("[sigsegv_handler] Installed crash handler for PID {}", std::process::id());
     }
 }
 